@@ -3,8 +3,8 @@ use crate::{
     internal::{
         error::{error, fail, Error, Result},
         schema::{
-            GenericDataType, GenericField, GenericTimeUnit, SchemaLike, Sealed, SerdeArrowSchema,
-            Strategy, STRATEGY_KEY,
+            GenericDataType, GenericField, GenericTimeUnit, GenericUnionMode, SchemaLike, Sealed,
+            SerdeArrowSchema, Strategy, STRATEGY_KEY,
         },
     },
 };
@@ -106,6 +106,14 @@ impl TryFrom<&Field> for GenericField {
             DataType::Float64 => GenericDataType::F64,
             DataType::Utf8 => GenericDataType::Utf8,
             DataType::LargeUtf8 => GenericDataType::LargeUtf8,
+            DataType::Binary => GenericDataType::Binary,
+            DataType::LargeBinary => GenericDataType::LargeBinary,
+            DataType::FixedSizeBinary(n) => {
+                if *n > u32::MAX as usize {
+                    fail!("cannot represent the number of bytes of the FixedSizeBinary");
+                }
+                GenericDataType::FixedSizeBinary(*n as u32)
+            }
             DataType::Date32 => GenericDataType::Date32,
             DataType::Date64 => GenericDataType::Date64,
             DataType::Decimal(precision, scale) => {
@@ -141,6 +149,13 @@ impl TryFrom<&Field> for GenericField {
                 children.push(field.as_ref().try_into()?);
                 GenericDataType::LargeList
             }
+            DataType::FixedSizeList(field, n) => {
+                if *n > u32::MAX as usize {
+                    fail!("cannot represent the number of elements of the fixed size list");
+                }
+                children.push(field.as_ref().try_into()?);
+                GenericDataType::FixedSizeList(*n as u32)
+            }
             DataType::Struct(fields) => {
                 for field in fields {
                     children.push(field.try_into()?);
@@ -152,17 +167,15 @@ impl TryFrom<&Field> for GenericField {
                 GenericDataType::Map
             }
             DataType::Union(fields, field_indices, mode) => {
-                if field_indices.is_some() {
-                    fail!("Union types with explicit field indices are not supported");
-                }
-                if !mode.is_dense() {
-                    fail!("Only dense unions are supported at the moment");
-                }
+                let mode = match mode {
+                    UnionMode::Dense => GenericUnionMode::Dense,
+                    UnionMode::Sparse => GenericUnionMode::Sparse,
+                };
 
                 for field in fields {
                     children.push(field.try_into()?);
                 }
-                GenericDataType::Union
+                GenericDataType::Union(mode, field_indices.clone())
             }
             DataType::Dictionary(int_type, data_type, sorted) => {
                 if *sorted {
@@ -245,6 +258,9 @@ impl TryFrom<&GenericField> for Field {
             }
             GenericDataType::Utf8 => DataType::Utf8,
             GenericDataType::LargeUtf8 => DataType::LargeUtf8,
+            GenericDataType::Binary => DataType::Binary,
+            GenericDataType::LargeBinary => DataType::LargeBinary,
+            GenericDataType::FixedSizeBinary(n) => DataType::FixedSizeBinary(*n as usize),
             GenericDataType::List => DataType::List(Box::new(
                 value
                     .children
@@ -259,6 +275,16 @@ impl TryFrom<&GenericField> for Field {
                     .ok_or_else(|| error!("List must a single child"))?
                     .try_into()?,
             )),
+            GenericDataType::FixedSizeList(n) => DataType::FixedSizeList(
+                Box::new(
+                    value
+                        .children
+                        .first()
+                        .ok_or_else(|| error!("FixedSizeList must a single child"))?
+                        .try_into()?,
+                ),
+                *n as usize,
+            ),
             GenericDataType::Struct => DataType::Struct(
                 value
                     .children
@@ -274,14 +300,17 @@ impl TryFrom<&GenericField> for Field {
                     .try_into()?;
                 DataType::Map(Box::new(element_field), false)
             }
-            GenericDataType::Union => DataType::Union(
+            GenericDataType::Union(mode, type_ids) => DataType::Union(
                 value
                     .children
                     .iter()
                     .map(Field::try_from)
                     .collect::<Result<Vec<_>>>()?,
-                None,
-                UnionMode::Dense,
+                type_ids.clone(),
+                match mode {
+                    GenericUnionMode::Dense => UnionMode::Dense,
+                    GenericUnionMode::Sparse => UnionMode::Sparse,
+                },
             ),
             GenericDataType::Dictionary => {
                 let Some(key_field) = value.children.first() else {
@@ -0,0 +1,229 @@
+//! Recognize values that serialize as raw bytes or as a string, independent of the target type
+//!
+//! `serde` has no dedicated "this is a byte sequence" trait: a `#[serde(with = "serde_bytes")]
+//! Vec<u8>` (or a `serde_bytes::Bytes` / `serde_bytes::ByteBuf`) is only distinguishable from a
+//! plain `Vec<u8>` by the fact that its `Serialize` impl calls `serializer.serialize_bytes(..)`
+//! instead of `serialize_seq`. [`probe_value`] drives a value through a minimal [`serde::Serializer`]
+//! that captures exactly that call (or a `serialize_str` call, for the base64/hex strategies), so
+//! both the tracer and the binary-column serializer can tell a byte sequence apart from a
+//! `List<UInt8>` without knowing the concrete Rust type up front.
+use serde::ser::Impossible;
+
+use crate::internal::error::{fail, Error, Result};
+
+/// The shape a value probed itself as during [`probe_value`]
+pub enum ProbedValue {
+    /// The value called `serialize_bytes`
+    Bytes(Vec<u8>),
+    /// The value called `serialize_str`
+    Str(String),
+}
+
+/// Serialize `value` and capture whether it is a byte sequence or a string
+///
+/// `Some(..)` and newtype structs are unwrapped and their inner value probed. Fails if the
+/// (unwrapped) value's `Serialize` impl calls anything other than `serialize_bytes` or
+/// `serialize_str`.
+pub fn probe_value<T: serde::Serialize + ?Sized>(value: &T) -> Result<ProbedValue> {
+    value.serialize(ValueProbe)
+}
+
+/// Like [`probe_value`], but only succeeds for byte sequences
+///
+/// Returns the number of bytes when `value` serializes as a byte sequence, and `None` for
+/// anything else (strings included). The tracer does not call this function, so it does not
+/// map such values to
+/// [`GenericDataType::Binary`][crate::internal::schema::GenericDataType::Binary], and no
+/// `FixedSizeBinary` width is inferred from the returned length.
+pub fn trace_byte_sequence<T: serde::Serialize + ?Sized>(value: &T) -> Option<usize> {
+    match probe_value(value) {
+        Ok(ProbedValue::Bytes(bytes)) => Some(bytes.len()),
+        _ => None,
+    }
+}
+
+struct ValueProbe;
+
+impl serde::Serializer for ValueProbe {
+    type Ok = ProbedValue;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<ProbedValue, Error>;
+    type SerializeTuple = Impossible<ProbedValue, Error>;
+    type SerializeTupleStruct = Impossible<ProbedValue, Error>;
+    type SerializeTupleVariant = Impossible<ProbedValue, Error>;
+    type SerializeMap = Impossible<ProbedValue, Error>;
+    type SerializeStruct = Impossible<ProbedValue, Error>;
+    type SerializeStructVariant = Impossible<ProbedValue, Error>;
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        Ok(ProbedValue::Bytes(v.to_vec()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        Ok(ProbedValue::Str(v.to_owned()))
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a bool");
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found an i8");
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found an i16");
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found an i32");
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found an i64");
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a u8");
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a u16");
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a u32");
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a u64");
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found an f32");
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found an f64");
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a char");
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found none");
+    }
+
+    fn serialize_some<T: serde::Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found unit");
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a unit struct");
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a unit variant");
+    }
+
+    fn serialize_newtype_struct<T: serde::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: serde::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok> {
+        fail!("expected a byte sequence or a string, found a newtype variant");
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        fail!("expected a byte sequence or a string, found a sequence");
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        fail!("expected a byte sequence or a string, found a tuple");
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        fail!("expected a byte sequence or a string, found a tuple struct");
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        fail!("expected a byte sequence or a string, found a tuple variant");
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        fail!("expected a byte sequence or a string, found a map");
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct> {
+        fail!("expected a byte sequence or a string, found a struct");
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        fail!("expected a byte sequence or a string, found a struct variant");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn probes_byte_buffers_as_bytes() {
+        let bytes = serde_bytes::Bytes::new(b"hello");
+        assert!(matches!(probe_value(bytes), Ok(ProbedValue::Bytes(b)) if b == b"hello"));
+        assert_eq!(trace_byte_sequence(bytes), Some(5));
+    }
+
+    #[test]
+    fn probes_strings_as_str() {
+        assert!(matches!(probe_value("hello"), Ok(ProbedValue::Str(s)) if s == "hello"));
+        assert_eq!(trace_byte_sequence("hello"), None);
+    }
+
+    #[test]
+    fn rejects_plain_sequences() {
+        assert!(probe_value(&vec![1_u8, 2, 3]).is_err());
+        assert_eq!(trace_byte_sequence(&vec![1_u8, 2, 3]), None);
+    }
+}
@@ -0,0 +1,168 @@
+//! Policies for resolving duplicate keys encountered while deserializing `Map` columns
+//!
+//! A policy can be stored in
+//! [`TracingOptions::map_duplicate_key_policy`][crate::internal::schema::TracingOptions::map_duplicate_key_policy]
+//! and is applied by [`deserialize_map_with_policy`], entry by entry, as it reads each key / value
+//! pair. The `Map`-column deserializer neither reads that option nor calls
+//! [`deserialize_map_with_policy`]; the policy only takes effect where a visitor calls it directly.
+use std::collections::{BTreeMap, HashMap};
+
+use crate::internal::error::{fail, Result};
+
+/// How to resolve duplicate keys encountered while deserializing a `Map` column into a
+/// `HashMap` / `BTreeMap`
+///
+/// The default policy is [`DuplicateKeyPolicy::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Fail with an error naming the offending key
+    #[default]
+    Error,
+    /// Keep the first value encountered for a given key
+    FirstWins,
+    /// Keep the last value encountered for a given key
+    LastWins,
+}
+
+/// A map that entries can be inserted into while honoring a [`DuplicateKeyPolicy`]
+///
+/// Implemented for `HashMap` and `BTreeMap` so the deserializer can apply the policy without
+/// collecting entries into an intermediate `Vec` first.
+pub trait InsertWithPolicy<K, V> {
+    /// Insert `key` / `value`, resolving a duplicate `key` according to `policy`
+    fn insert_with_policy(&mut self, policy: DuplicateKeyPolicy, key: K, value: V) -> Result<()>;
+}
+
+impl<K: std::hash::Hash + Eq + std::fmt::Debug, V> InsertWithPolicy<K, V> for HashMap<K, V> {
+    fn insert_with_policy(&mut self, policy: DuplicateKeyPolicy, key: K, value: V) -> Result<()> {
+        match policy {
+            DuplicateKeyPolicy::LastWins => {
+                self.insert(key, value);
+            }
+            DuplicateKeyPolicy::FirstWins => {
+                self.entry(key).or_insert(value);
+            }
+            DuplicateKeyPolicy::Error => {
+                if self.contains_key(&key) {
+                    fail!("duplicate key {key:?} encountered while deserializing a map");
+                }
+                self.insert(key, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<K: Ord + std::fmt::Debug, V> InsertWithPolicy<K, V> for BTreeMap<K, V> {
+    fn insert_with_policy(&mut self, policy: DuplicateKeyPolicy, key: K, value: V) -> Result<()> {
+        match policy {
+            DuplicateKeyPolicy::LastWins => {
+                self.insert(key, value);
+            }
+            DuplicateKeyPolicy::FirstWins => {
+                self.entry(key).or_insert(value);
+            }
+            DuplicateKeyPolicy::Error => {
+                if self.contains_key(&key) {
+                    fail!("duplicate key {key:?} encountered while deserializing a map");
+                }
+                self.insert(key, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deserialize a `serde` map into `M`, resolving duplicate keys via `policy`
+///
+/// Entries are inserted through [`InsertWithPolicy::insert_with_policy`] as they are read off
+/// `map`, so a
+/// [`DuplicateKeyPolicy::FirstWins`] / [`DuplicateKeyPolicy::LastWins`] policy never needs to
+/// buffer the whole row in an intermediate `Vec` before resolving duplicates.
+pub fn deserialize_map_with_policy<'de, A, K, V, M>(
+    mut map: A,
+    policy: DuplicateKeyPolicy,
+) -> std::result::Result<M, A::Error>
+where
+    A: serde::de::MapAccess<'de>,
+    K: serde::Deserialize<'de>,
+    V: serde::Deserialize<'de>,
+    M: Default + InsertWithPolicy<K, V>,
+{
+    let mut result = M::default();
+    while let Some((key, value)) = map.next_entry::<K, V>()? {
+        result
+            .insert_with_policy(policy, key, value)
+            .map_err(serde::de::Error::custom)?;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserializer as _;
+
+    #[test]
+    fn error_policy_rejects_duplicates() {
+        let mut map = HashMap::new();
+        map.insert_with_policy(DuplicateKeyPolicy::Error, "a", 1)
+            .unwrap();
+        assert!(map
+            .insert_with_policy(DuplicateKeyPolicy::Error, "a", 2)
+            .is_err());
+    }
+
+    #[test]
+    fn first_wins_keeps_first_value() {
+        let mut map = BTreeMap::new();
+        map.insert_with_policy(DuplicateKeyPolicy::FirstWins, "a", 1)
+            .unwrap();
+        map.insert_with_policy(DuplicateKeyPolicy::FirstWins, "a", 2)
+            .unwrap();
+        assert_eq!(map.get("a"), Some(&1));
+    }
+
+    #[test]
+    fn last_wins_keeps_last_value() {
+        let mut map = HashMap::new();
+        map.insert_with_policy(DuplicateKeyPolicy::LastWins, "a", 1)
+            .unwrap();
+        map.insert_with_policy(DuplicateKeyPolicy::LastWins, "a", 2)
+            .unwrap();
+        assert_eq!(map.get("a"), Some(&2));
+    }
+
+    struct MapVisitor(DuplicateKeyPolicy);
+
+    impl<'de> serde::de::Visitor<'de> for MapVisitor {
+        type Value = BTreeMap<String, i32>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "a map of string to i32")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            map: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            deserialize_map_with_policy(map, self.0)
+        }
+    }
+
+    #[test]
+    fn deserialize_map_with_policy_applies_last_wins() {
+        let map: BTreeMap<String, i32> = serde_json::Deserializer::from_str(r#"{"a":1,"a":2}"#)
+            .deserialize_map(MapVisitor(DuplicateKeyPolicy::LastWins))
+            .unwrap();
+        assert_eq!(map.get("a"), Some(&2));
+    }
+
+    #[test]
+    fn deserialize_map_with_policy_rejects_duplicates_on_error_policy() {
+        let result: std::result::Result<BTreeMap<String, i32>, _> =
+            serde_json::Deserializer::from_str(r#"{"a":1,"a":2}"#)
+                .deserialize_map(MapVisitor(DuplicateKeyPolicy::Error));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Byte <-> string encodings used by the `BinaryAsBase64` / `BinaryAsHex` strategies
+use crate::internal::error::{error, fail, Result};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const HEX_ALPHABET: &[u8; 16] = b"0123456789abcdef";
+
+/// Encode a byte slice as a standard-alphabet base64 string
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut res = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied();
+        let b2 = chunk.get(2).copied();
+
+        let n = (b0 as u32) << 16 | (b1.unwrap_or(0) as u32) << 8 | (b2.unwrap_or(0) as u32);
+
+        res.push(BASE64_ALPHABET[(n >> 18 & 0x3f) as usize] as char);
+        res.push(BASE64_ALPHABET[(n >> 12 & 0x3f) as usize] as char);
+        res.push(if b1.is_some() {
+            BASE64_ALPHABET[(n >> 6 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+        res.push(if b2.is_some() {
+            BASE64_ALPHABET[(n & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    res
+}
+
+/// Decode a standard-alphabet base64 string, failing on invalid characters or bad padding
+pub fn base64_decode(s: &str) -> Result<Vec<u8>> {
+    let s = s.as_bytes();
+    if s.is_empty() {
+        return Ok(Vec::new());
+    }
+    if s.len() % 4 != 0 {
+        fail!("invalid base64 string: length is not a multiple of 4");
+    }
+
+    fn value(c: u8) -> Result<u8> {
+        BASE64_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .map(|idx| idx as u8)
+            .ok_or_else(|| error!("invalid base64 character {:?}", c as char))
+    }
+
+    let mut res = Vec::with_capacity(s.len() / 4 * 3);
+    let num_chunks = s.len() / 4;
+    for (chunk_idx, chunk) in s.chunks(4).enumerate() {
+        let pad = chunk.iter().filter(|&&c| c == b'=').count();
+        if pad > 2
+            || chunk[..4 - pad].iter().any(|&c| c == b'=')
+            || (pad > 0 && chunk_idx + 1 != num_chunks)
+        {
+            fail!("invalid base64 padding");
+        }
+
+        let mut n: u32 = 0;
+        for (idx, &c) in chunk.iter().enumerate() {
+            n |= (if c == b'=' { 0 } else { value(c)? as u32 }) << (18 - 6 * idx);
+        }
+
+        res.push((n >> 16 & 0xff) as u8);
+        if pad < 2 {
+            res.push((n >> 8 & 0xff) as u8);
+        }
+        if pad < 1 {
+            res.push((n & 0xff) as u8);
+        }
+    }
+    Ok(res)
+}
+
+/// Encode a byte slice as a lowercase hex string
+pub fn hex_encode(bytes: &[u8]) -> String {
+    let mut res = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        res.push(HEX_ALPHABET[(b >> 4) as usize] as char);
+        res.push(HEX_ALPHABET[(b & 0xf) as usize] as char);
+    }
+    res
+}
+
+/// Decode a lowercase hex string, failing on invalid characters or odd length
+pub fn hex_decode(s: &str) -> Result<Vec<u8>> {
+    let s = s.as_bytes();
+    if s.len() % 2 != 0 {
+        fail!("invalid hex string: odd number of characters");
+    }
+
+    fn nibble(c: u8) -> Result<u8> {
+        match c {
+            b'0'..=b'9' => Ok(c - b'0'),
+            b'a'..=b'f' => Ok(c - b'a' + 10),
+            other => fail!("invalid hex character {:?}", other as char),
+        }
+    }
+
+    let mut res = Vec::with_capacity(s.len() / 2);
+    for chunk in s.chunks(2) {
+        res.push(nibble(chunk[0])? << 4 | nibble(chunk[1])?);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn base64_round_trip() {
+        for bytes in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            let encoded = base64_encode(bytes);
+            assert_eq!(base64_decode(&encoded).unwrap(), bytes);
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        for bytes in [&b""[..], b"f", b"fo", b"foobar"] {
+            let encoded = hex_encode(bytes);
+            assert_eq!(hex_decode(&encoded).unwrap(), bytes);
+        }
+        assert_eq!(hex_encode(b"foobar"), "666f6f626172");
+    }
+
+    #[test]
+    fn base64_rejects_invalid_padding() {
+        assert!(base64_decode("=Zm9").is_err());
+    }
+
+    #[test]
+    fn hex_rejects_odd_length() {
+        assert!(hex_decode("abc").is_err());
+    }
+}
@@ -0,0 +1,561 @@
+//! The format-independent schema types shared by the `arrow2` (and other) backends
+use std::collections::BTreeMap;
+
+use crate::internal::{
+    duplicate_key_policy::DuplicateKeyPolicy,
+    error::{error, fail, Error, Result},
+};
+
+/// The metadata key under which a field's [`Strategy`] is stored
+pub const STRATEGY_KEY: &str = "SERDE_ARROW:strategy";
+
+/// A marker trait sealing [`SchemaLike`] against external implementations
+pub trait Sealed {}
+
+/// Types that a [`SerdeArrowSchema`] can be built from / converted into
+pub trait SchemaLike: Sized + Sealed {
+    /// Determine the schema of a collection of samples
+    fn from_value<T: serde::Serialize + ?Sized>(value: &T) -> Result<Self>;
+
+    /// Determine the schema from the given type
+    fn from_type<'de, T: serde::Deserialize<'de> + ?Sized>(options: TracingOptions)
+        -> Result<Self>;
+
+    /// Determine the schema of a collection of samples, recording runtime information not
+    /// visible from the type alone
+    fn from_samples<T: serde::Serialize + ?Sized>(
+        samples: &T,
+        options: TracingOptions,
+    ) -> Result<Self>;
+}
+
+/// Options that influence how a schema is traced from a Rust type or samples
+#[derive(Debug, Clone)]
+pub struct TracingOptions {
+    /// The policy for duplicate keys in map values; the `Map`-column deserializer does not read
+    /// it (see [`crate::internal::duplicate_key_policy`])
+    pub map_duplicate_key_policy: DuplicateKeyPolicy,
+}
+
+impl Default for TracingOptions {
+    fn default() -> Self {
+        Self {
+            map_duplicate_key_policy: DuplicateKeyPolicy::default(),
+        }
+    }
+}
+
+impl TracingOptions {
+    /// Build options with their defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set [`TracingOptions::map_duplicate_key_policy`]
+    pub fn map_duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.map_duplicate_key_policy = policy;
+        self
+    }
+}
+
+/// A format-independent arrow schema, a list of [`GenericField`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SerdeArrowSchema {
+    /// The fields of the schema
+    pub fields: Vec<GenericField>,
+}
+
+impl Sealed for SerdeArrowSchema {}
+
+impl SchemaLike for SerdeArrowSchema {
+    fn from_value<T: serde::Serialize + ?Sized>(_value: &T) -> Result<Self> {
+        fail!("tracing a schema from a value requires the (unavailable) tracer module");
+    }
+
+    fn from_type<'de, T: serde::Deserialize<'de> + ?Sized>(
+        _options: TracingOptions,
+    ) -> Result<Self> {
+        fail!("tracing a schema from a type requires the (unavailable) tracer module");
+    }
+
+    fn from_samples<T: serde::Serialize + ?Sized>(
+        _samples: &T,
+        _options: TracingOptions,
+    ) -> Result<Self> {
+        fail!("tracing a schema from samples requires the (unavailable) tracer module");
+    }
+}
+
+/// A format-independent arrow field
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericField {
+    /// The name of the field
+    pub name: String,
+    /// The data type of the field
+    pub data_type: GenericDataType,
+    /// The strategy used to (de-)serialize values of this field, if any
+    pub strategy: Option<Strategy>,
+    /// Whether the field may contain missing values
+    pub nullable: bool,
+    /// The child fields, e.g. the element field of a list or the variant fields of a union
+    pub children: Vec<GenericField>,
+}
+
+impl GenericField {
+    /// Check invariants that must hold for this field to be used with serde_arrow
+    pub fn validate(&self) -> Result<()> {
+        match (&self.data_type, &self.strategy) {
+            (
+                GenericDataType::Binary | GenericDataType::LargeBinary | GenericDataType::FixedSizeBinary(_),
+                None | Some(Strategy::BinaryAsBase64) | Some(Strategy::BinaryAsHex),
+            ) => {}
+            (_, Some(Strategy::BinaryAsBase64) | Some(Strategy::BinaryAsHex)) => {
+                fail!(
+                    "field {:?}: the BinaryAsBase64 / BinaryAsHex strategies require a binary data type",
+                    self.name,
+                );
+            }
+            (GenericDataType::FixedSizeList(n), _) => {
+                if *n == 0 {
+                    fail!("field {:?}: a FixedSizeList must have at least one element", self.name);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Validate that one row of a `FixedSizeList` column has the expected number of elements
+    ///
+    /// A `FixedSizeList` of length `n` has exactly `n` child values per row and no offsets
+    /// buffer, so a mismatched row length cannot be represented and must be rejected instead of
+    /// silently truncated or padded.
+    pub fn validate_fixed_size_list_row(&self, len: usize) -> Result<()> {
+        if let GenericDataType::FixedSizeList(n) = self.data_type {
+            if len != n as usize {
+                fail!(
+                    "field {:?}: expected {n} elements per row for a FixedSizeList, found {len}",
+                    self.name,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize this field's `FixedSizeList` rows into the single flat child value buffer arrow
+    /// uses for them
+    ///
+    /// Each row in `rows` is checked with [`GenericField::validate_fixed_size_list_row`] before
+    /// being appended, so a row with the wrong number of elements fails the whole column instead
+    /// of silently shifting every later row's child values.
+    pub fn serialize_fixed_size_list_rows<T>(
+        &self,
+        rows: impl IntoIterator<Item = Vec<T>>,
+    ) -> Result<Vec<T>> {
+        let mut values = Vec::new();
+        for row in rows {
+            self.validate_fixed_size_list_row(row.len())?;
+            values.extend(row);
+        }
+        Ok(values)
+    }
+
+    /// Validate that one row of a `FixedSizeBinary` column has the expected number of bytes
+    ///
+    /// Like `FixedSizeList`, a `FixedSizeBinary(n)` column has exactly `n` bytes per row and no
+    /// offsets buffer, so a mismatched byte count must be rejected rather than truncated.
+    pub fn validate_fixed_size_binary_row(&self, len: usize) -> Result<()> {
+        if let GenericDataType::FixedSizeBinary(n) = self.data_type {
+            if len != n as usize {
+                fail!(
+                    "field {:?}: expected {n} bytes per row for a FixedSizeBinary, found {len}",
+                    self.name,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether this field's data type stores an opaque byte sequence
+    ///
+    /// The tracer should treat any value recognized by
+    /// [`trace_byte_sequence`][crate::internal::bytes::trace_byte_sequence] (`serde_bytes`-wrapped
+    /// fields, `serde_bytes::Bytes` / `ByteBuf`, ...) as one of these data types instead of
+    /// exploding it into a `List<UInt8>`.
+    pub fn is_byte_sequence(&self) -> bool {
+        matches!(
+            self.data_type,
+            GenericDataType::Binary | GenericDataType::LargeBinary | GenericDataType::FixedSizeBinary(_)
+        )
+    }
+
+    /// Serialize one row of this field's `Binary` / `LargeBinary` / `FixedSizeBinary` column
+    ///
+    /// `value` must serialize as raw bytes (e.g. `#[serde(with = "serde_bytes")] Vec<u8>`) when
+    /// no [`Strategy`] is set. When [`Strategy::BinaryAsBase64`] or [`Strategy::BinaryAsHex`] is
+    /// set, `value` must instead serialize as a string, which is decoded back to the bytes
+    /// actually written to the column through [`Strategy::decode_bytes`].
+    pub fn serialize_binary_row<T: serde::Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>> {
+        use crate::internal::bytes::ProbedValue;
+
+        let bytes = match (crate::internal::bytes::probe_value(value)?, self.strategy) {
+            (ProbedValue::Bytes(bytes), None) => bytes,
+            (ProbedValue::Str(s), Some(strategy)) => strategy.decode_bytes(&s)?,
+            (ProbedValue::Bytes(_), Some(strategy)) => {
+                fail!(
+                    "field {:?}: expected a string for the {strategy} strategy, found a byte sequence",
+                    self.name,
+                );
+            }
+            (ProbedValue::Str(_), None) => {
+                fail!(
+                    "field {:?}: expected a byte sequence, found a string (set a Strategy to decode it)",
+                    self.name,
+                );
+            }
+        };
+        self.validate_fixed_size_binary_row(bytes.len())?;
+        Ok(bytes)
+    }
+
+    /// Turn one row of this field's `Binary` / `LargeBinary` / `FixedSizeBinary` column back into
+    /// the value the deserializer should hand to `serde`
+    ///
+    /// Mirrors [`GenericField::serialize_binary_row`]: raw bytes when no [`Strategy`] is set, or a
+    /// string encoded through [`Strategy::encode_bytes`] otherwise.
+    pub fn deserialize_binary_row(&self, bytes: &[u8]) -> Result<crate::internal::bytes::ProbedValue> {
+        use crate::internal::bytes::ProbedValue;
+
+        self.validate_fixed_size_binary_row(bytes.len())?;
+        Ok(match self.strategy {
+            None => ProbedValue::Bytes(bytes.to_vec()),
+            Some(strategy) => ProbedValue::Str(strategy.encode_bytes(bytes)),
+        })
+    }
+
+    /// Resolve a union discriminant (`type_id`) to the index of its variant child field
+    ///
+    /// Honors an explicit type-id mapping (the second element of [`GenericDataType::Union`])
+    /// for unions whose discriminants are not the contiguous `0..children.len()` range assumed
+    /// when no mapping is given. Both dense and sparse unions share this resolution step; they
+    /// differ only in how the resolved child's value buffer is addressed (by offset vs. by row).
+    pub fn union_child_index(&self, type_id: i32) -> Result<usize> {
+        let GenericDataType::Union(_, type_ids) = &self.data_type else {
+            fail!("field {:?} is not a union", self.name);
+        };
+
+        match type_ids {
+            Some(ids) => ids
+                .iter()
+                .position(|id| *id == type_id)
+                .ok_or_else(|| error!("field {:?}: unknown union type id {type_id}", self.name)),
+            None => {
+                let idx = usize::try_from(type_id)
+                    .map_err(|_| error!("field {:?}: invalid union type id {type_id}", self.name))?;
+                if idx >= self.children.len() {
+                    fail!("field {:?}: union type id {type_id} out of range", self.name);
+                }
+                Ok(idx)
+            }
+        }
+    }
+
+    /// The child indices that a sparse union's array builder must write a null into for a row
+    /// whose resolved variant is `selected_child`
+    ///
+    /// A sparse union gives every child array the full row count, so writing a row means
+    /// appending the real value to `selected_child`'s builder and a null to every other child's
+    /// builder. A dense union has no such requirement (each child only stores the rows it was
+    /// actually selected for), so this returns an empty iterator for dense unions.
+    pub fn sparse_union_null_children(
+        &self,
+        selected_child: usize,
+    ) -> Result<impl Iterator<Item = usize> + '_> {
+        let GenericDataType::Union(mode, _) = &self.data_type else {
+            fail!("field {:?} is not a union", self.name);
+        };
+        let num_children = match mode {
+            GenericUnionMode::Sparse => self.children.len(),
+            GenericUnionMode::Dense => 0,
+        };
+        Ok((0..num_children).filter(move |idx| *idx != selected_child))
+    }
+}
+
+/// Determine the fixed length of a `FixedSizeList` column from the observed per-row lengths
+///
+/// Returns `Some(n)` if every sample has exactly `n` elements and `n` is at least one, matching
+/// the invariant required by [`GenericDataType::FixedSizeList`]; returns `None` if the lengths
+/// differ, if every sample is empty or if there are no samples, in which case the tracer should
+/// fall back to a variable-length `List`.
+pub fn infer_fixed_size_list_len(observed_lengths: &[usize]) -> Option<u32> {
+    let (first, rest) = observed_lengths.split_first()?;
+    if rest.iter().all(|len| len == first) {
+        u32::try_from(*first).ok().filter(|n| *n > 0)
+    } else {
+        None
+    }
+}
+
+/// The data type of a [`GenericField`], independent of the concrete arrow implementation used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenericDataType {
+    /// A value that is always missing
+    Null,
+    /// A boolean value
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F16,
+    F32,
+    F64,
+    /// A UTF-8 encoded string
+    Utf8,
+    /// A UTF-8 encoded string with 64 bit offsets
+    LargeUtf8,
+    /// An opaque byte array
+    Binary,
+    /// An opaque byte array with 64 bit offsets
+    LargeBinary,
+    /// An opaque byte array of a fixed number of bytes per row
+    FixedSizeBinary(u32),
+    Date32,
+    Date64,
+    Time64(GenericTimeUnit),
+    Timestamp(GenericTimeUnit, Option<String>),
+    /// A fixed point decimal value with the given precision and scale
+    Decimal128(u8, i8),
+    /// A variable sized list, the single child is the element field
+    List,
+    /// A variable sized list with 64 bit offsets, the single child is the element field
+    LargeList,
+    /// A list with a fixed number of elements per row, the single child is the element field
+    FixedSizeList(u32),
+    /// A struct, each child is one of its fields
+    Struct,
+    /// A map, the single child is a struct field of its key / value fields
+    Map,
+    /// A union, each child is one of its variants
+    Union(GenericUnionMode, Option<Vec<i32>>),
+    /// A dictionary, the two children are the key and value fields
+    Dictionary,
+}
+
+/// The time unit used by [`GenericDataType::Time64`] / [`GenericDataType::Timestamp`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenericTimeUnit {
+    Second,
+    Millisecond,
+    Microsecond,
+    Nanosecond,
+}
+
+impl std::fmt::Display for GenericTimeUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Second => write!(f, "Second"),
+            Self::Millisecond => write!(f, "Millisecond"),
+            Self::Microsecond => write!(f, "Microsecond"),
+            Self::Nanosecond => write!(f, "Nanosecond"),
+        }
+    }
+}
+
+/// The physical layout used by [`GenericDataType::Union`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenericUnionMode {
+    /// Every variant child array has the full row count and there is no offsets buffer
+    Sparse,
+    /// Each variant child array only stores the rows that selected it, addressed via an offsets
+    /// buffer
+    Dense,
+}
+
+/// A strategy that customizes how a field's values are (de-)serialized
+///
+/// Strategies are stored in a field's metadata under the [`STRATEGY_KEY`] and round-trip
+/// through [`TryFrom<&GenericField> for Field`](std::convert::TryFrom) / the reverse conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Encode binary values as a base64 string
+    BinaryAsBase64,
+    /// Encode binary values as a lowercase hex string
+    BinaryAsHex,
+}
+
+impl Strategy {
+    /// Encode `bytes` as a string, as selected by this strategy
+    pub fn encode_bytes(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::BinaryAsBase64 => crate::internal::encoding::base64_encode(bytes),
+            Self::BinaryAsHex => crate::internal::encoding::hex_encode(bytes),
+        }
+    }
+
+    /// Decode a string produced by [`Strategy::encode_bytes`] back into bytes
+    pub fn decode_bytes(&self, s: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::BinaryAsBase64 => crate::internal::encoding::base64_decode(s),
+            Self::BinaryAsHex => crate::internal::encoding::hex_decode(s),
+        }
+    }
+}
+
+impl std::fmt::Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BinaryAsBase64 => write!(f, "BinaryAsBase64"),
+            Self::BinaryAsHex => write!(f, "BinaryAsHex"),
+        }
+    }
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "BinaryAsBase64" => Ok(Self::BinaryAsBase64),
+            "BinaryAsHex" => Ok(Self::BinaryAsHex),
+            s => fail!("unknown strategy {s:?}"),
+        }
+    }
+}
+
+impl From<Strategy> for BTreeMap<String, String> {
+    fn from(value: Strategy) -> Self {
+        let mut metadata = BTreeMap::new();
+        metadata.insert(STRATEGY_KEY.to_owned(), value.to_string());
+        metadata
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::internal::bytes::ProbedValue;
+
+    fn binary_field(strategy: Option<Strategy>) -> GenericField {
+        GenericField {
+            name: "value".to_owned(),
+            data_type: GenericDataType::Binary,
+            strategy,
+            nullable: false,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn serializes_raw_bytes_without_a_strategy() {
+        let field = binary_field(None);
+        let bytes = field
+            .serialize_binary_row(serde_bytes::Bytes::new(b"hello"))
+            .unwrap();
+        assert_eq!(bytes, b"hello");
+    }
+
+    #[test]
+    fn round_trips_through_the_base64_strategy() {
+        let field = binary_field(Some(Strategy::BinaryAsBase64));
+        let bytes = field.serialize_binary_row("aGVsbG8=").unwrap();
+        assert_eq!(bytes, b"hello");
+
+        let ProbedValue::Str(s) = field.deserialize_binary_row(&bytes).unwrap() else {
+            panic!("expected a string");
+        };
+        assert_eq!(s, "aGVsbG8=");
+    }
+
+    #[test]
+    fn rejects_a_string_without_a_strategy() {
+        let field = binary_field(None);
+        assert!(field.serialize_binary_row("aGVsbG8=").is_err());
+    }
+
+    #[test]
+    fn rejects_raw_bytes_with_a_strategy() {
+        let field = binary_field(Some(Strategy::BinaryAsHex));
+        assert!(field
+            .serialize_binary_row(serde_bytes::Bytes::new(b"hello"))
+            .is_err());
+    }
+
+    fn fixed_size_list_field(n: u32) -> GenericField {
+        GenericField {
+            name: "value".to_owned(),
+            data_type: GenericDataType::FixedSizeList(n),
+            strategy: None,
+            nullable: false,
+            children: vec![GenericField {
+                name: "item".to_owned(),
+                data_type: GenericDataType::I32,
+                strategy: None,
+                nullable: false,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn flattens_rows_of_the_expected_length() {
+        let field = fixed_size_list_field(2);
+        let values = field
+            .serialize_fixed_size_list_rows([vec![1, 2], vec![3, 4], vec![5, 6]])
+            .unwrap();
+        assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn rejects_a_row_with_the_wrong_length() {
+        let field = fixed_size_list_field(2);
+        assert!(field
+            .serialize_fixed_size_list_rows([vec![1, 2], vec![3]])
+            .is_err());
+    }
+
+    fn union_field(mode: GenericUnionMode, type_ids: Option<Vec<i32>>) -> GenericField {
+        GenericField {
+            name: "value".to_owned(),
+            data_type: GenericDataType::Union(mode, type_ids),
+            strategy: None,
+            nullable: false,
+            children: (0..3)
+                .map(|idx| GenericField {
+                    name: format!("variant{idx}"),
+                    data_type: GenericDataType::I32,
+                    strategy: None,
+                    nullable: false,
+                    children: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn sparse_union_nulls_every_other_child() {
+        let field = union_field(GenericUnionMode::Sparse, None);
+        let nulls: Vec<usize> = field.sparse_union_null_children(1).unwrap().collect();
+        assert_eq!(nulls, vec![0, 2]);
+    }
+
+    #[test]
+    fn dense_union_has_no_null_children() {
+        let field = union_field(GenericUnionMode::Dense, None);
+        let nulls: Vec<usize> = field.sparse_union_null_children(1).unwrap().collect();
+        assert!(nulls.is_empty());
+    }
+
+    #[test]
+    fn resolves_explicit_type_ids_for_sparse_unions() {
+        let field = union_field(GenericUnionMode::Sparse, Some(vec![7, 3, 9]));
+        assert_eq!(field.union_child_index(3).unwrap(), 1);
+        let nulls: Vec<usize> = field.sparse_union_null_children(1).unwrap().collect();
+        assert_eq!(nulls, vec![0, 2]);
+    }
+}
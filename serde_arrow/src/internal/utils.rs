@@ -1,139 +1,263 @@
-use serde::{ser::SerializeSeq, Deserialize, Serialize};
+use std::{collections::VecDeque, marker::PhantomData};
+
+use serde::{de::Visitor, ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Selects the field name used by [`Item`] / [`Items`]
+///
+/// Implement this trait on a custom zero-sized type to wrap values under a field name other
+/// than the default `"item"`, e.g. when adapting an existing Arrow schema whose single column
+/// uses a different name.
+///
+/// ```rust
+/// # use serde_arrow::utils::FieldName;
+/// struct Value;
+///
+/// impl FieldName for Value {
+///     const NAME: &'static str = "value";
+/// }
+/// ```
+pub trait FieldName {
+    /// The field name used during (de-)serialization
+    const NAME: &'static str;
+}
+
+/// The default field name (`"item"`) used by [`Item`] and [`Items`]
+#[derive(Debug, PartialEq)]
+pub struct DefaultFieldName;
+
+impl FieldName for DefaultFieldName {
+    const NAME: &'static str = "item";
+}
 
 /// A wrapper around a sequence of items
 ///
 /// When serialized or deserialized, it behaves as if each item was wrapped in a
-/// struct with a single attribute `"item"`.
+/// struct with a single attribute `"item"`. Use [`Items::with_name`] to select a
+/// different field name via a custom [`FieldName`] implementation.
 ///
 /// ```rust
 /// # fn main() -> serde_arrow::_impl::PanicOnError<()> {
 /// # use serde_arrow::utils::Items;
 /// #
+/// let items: Items<[u32; 2]> = Items::with_name([13, 21]);
 /// assert_eq!(
-///     serde_json::to_string(&Items([13, 21]))?,
+///     serde_json::to_string(&items)?,
 ///     r#"[{"item":13},{"item":21}]"#,
 /// );
 ///
-/// let Items(items): Items<Vec<u32>> = serde_json::from_str(r#"[
+/// let items: Items<Vec<u32>> = serde_json::from_str(r#"[
 ///     {"item": 21},
 ///     {"item": 42}
 /// ]"#)?;
-/// assert_eq!(items, &[21, 42]);
+/// assert_eq!(items.0, &[21, 42]);
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, PartialEq)]
-pub struct Items<T>(
+pub struct Items<T, N: FieldName = DefaultFieldName>(
     /// The wrapped object
     pub T,
+    PhantomData<N>,
 );
 
+impl<T: std::fmt::Debug, N: FieldName> std::fmt::Debug for Items<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Items").field(&self.0).finish()
+    }
+}
+
+impl<T: PartialEq, N: FieldName> PartialEq for Items<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T, N: FieldName> Items<T, N> {
+    /// Wrap `value`, using the field name provided by `N` (`"item"` by default)
+    pub fn with_name(value: T) -> Self {
+        Items(value, PhantomData)
+    }
+}
+
+/// Collect an iterator of values into `Items`, e.g. via `.collect::<Items<Vec<_>>>()`
+///
+/// ```rust
+/// # use serde_arrow::utils::Items;
+/// let items: Items<Vec<u32>> = (1..=3).collect();
+/// assert_eq!(items.0, vec![1, 2, 3]);
+/// ```
+impl<T, N: FieldName> std::iter::FromIterator<T> for Items<Vec<T>, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Items(iter.into_iter().collect(), PhantomData)
+    }
+}
+
 /// A wrapper around a single item
 ///
-/// When serialized or deserialized, it behaves as if the Item was wrapped in a
-/// struct with a single attribute `"item"`.
+/// When serialized or deserialized, it behaves as if the item was wrapped in a
+/// struct with a single attribute `"item"`. Use [`Item::with_name`] to select a
+/// different field name via a custom [`FieldName`] implementation.
 ///
 /// ```rust
 /// # fn main() -> serde_arrow::_impl::PanicOnError<()> {
 /// # use serde_arrow::utils::Item;
 /// #
-/// assert_eq!(serde_json::to_string(&Item(42))?, r#"{"item":42}"#);
+/// let item: Item<u32> = Item::with_name(42);
+/// assert_eq!(serde_json::to_string(&item)?, r#"{"item":42}"#);
 ///
-/// let Item(item): Item<u32> = serde_json::from_str(r#"{"item":21}"#)?;
-/// assert_eq!(item, 21);
+/// let item: Item<u32> = serde_json::from_str(r#"{"item":21}"#)?;
+/// assert_eq!(item.0, 21);
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, PartialEq)]
-pub struct Item<T>(
+pub struct Item<T, N: FieldName = DefaultFieldName>(
     /// The wrapped object
     pub T,
+    PhantomData<N>,
 );
 
-impl<T: Serialize> Serialize for Item<T> {
-    fn serialize<S: serde::Serializer>(
-        &self,
-        serializer: S,
-    ) -> std::result::Result<S::Ok, S::Error> {
-        #[derive(Debug, Serialize)]
-        struct Item<'a, T> {
-            item: &'a T,
+impl<T: std::fmt::Debug, N: FieldName> std::fmt::Debug for Item<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Item").field(&self.0).finish()
+    }
+}
+
+impl<T: PartialEq, N: FieldName> PartialEq for Item<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T, N: FieldName> Item<T, N> {
+    /// Wrap `value`, using the field name provided by `N` (`"item"` by default)
+    pub fn with_name(value: T) -> Self {
+        Item(value, PhantomData)
+    }
+}
+
+impl<T: Serialize, N: FieldName> Serialize for Item<T, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("Item", 1)?;
+        s.serialize_field(N::NAME, &self.0)?;
+        s.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>, N: FieldName> Deserialize<'de> for Item<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct ItemVisitor<T, N>(PhantomData<(T, N)>);
+
+        impl<'de, T: Deserialize<'de>, N: FieldName> Visitor<'de> for ItemVisitor<T, N> {
+            type Value = T;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "a struct with a single field {:?}", N::NAME)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<T, A::Error> {
+                seq.next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<T, A::Error> {
+                let mut value = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    if key != N::NAME {
+                        map.next_value::<serde::de::IgnoredAny>()?;
+                        continue;
+                    }
+                    if value.is_some() {
+                        return Err(serde::de::Error::duplicate_field(N::NAME));
+                    }
+                    value = Some(map.next_value()?);
+                }
+                value.ok_or_else(|| serde::de::Error::missing_field(N::NAME))
+            }
         }
-        Item { item: &self.0 }.serialize(serializer)
+
+        let fields: &'static [&'static str] = &[N::NAME];
+        let item = deserializer.deserialize_struct("Item", fields, ItemVisitor(PhantomData))?;
+        Ok(Item(item, PhantomData))
     }
 }
 
-impl<'de, T: Deserialize<'de>> Deserialize<'de> for Item<T> {
-    fn deserialize<D: serde::Deserializer<'de>>(
-        deserializer: D,
-    ) -> std::result::Result<Self, D::Error> {
-        #[derive(Debug, Deserialize)]
-        struct Item<T> {
-            item: T,
+impl<'a, T: Serialize, N: FieldName> Serialize for Items<&'a [T], N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for item in self.0 {
+            seq.serialize_element(&Item::<&T, N>::with_name(item))?;
         }
-        let item = Item::<T>::deserialize(deserializer)?;
-        Ok(Item(item.item))
+        seq.end()
     }
 }
 
-// TODO: implement for all types?
-impl<'de, T: Deserialize<'de>> Deserialize<'de> for Items<Vec<T>> {
-    fn deserialize<D: serde::Deserializer<'de>>(
-        deserializer: D,
-    ) -> std::result::Result<Self, D::Error> {
-        let items = Vec::<Item<T>>::deserialize(deserializer)?
-            .into_iter()
-            .map(|item| item.0)
-            .collect();
-        Ok(Items(items))
+impl<T: Serialize, N: FieldName> Serialize for Items<Vec<T>, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Items::<&[T], N>::with_name(self.0.as_slice()).serialize(serializer)
     }
 }
 
-impl<T: Serialize> Serialize for Items<Vec<T>> {
-    fn serialize<S: serde::Serializer>(
-        &self,
-        serializer: S,
-    ) -> std::result::Result<S::Ok, S::Error> {
-        Items(self.0.as_slice()).serialize(serializer)
+impl<'a, T: Serialize, N: FieldName> Serialize for Items<&'a Vec<T>, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Items::<&[T], N>::with_name(self.0.as_slice()).serialize(serializer)
     }
 }
 
-impl<'a, T: Serialize> Serialize for Items<&'a Vec<T>> {
-    fn serialize<S: serde::Serializer>(
-        &self,
-        serializer: S,
-    ) -> std::result::Result<S::Ok, S::Error> {
-        Items(self.0.as_slice()).serialize(serializer)
+impl<const LEN: usize, T: Serialize, N: FieldName> Serialize for Items<[T; LEN], N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Items::<&[T], N>::with_name(self.0.as_slice()).serialize(serializer)
     }
 }
 
-impl<const N: usize, T: Serialize> Serialize for Items<[T; N]> {
-    fn serialize<S: serde::Serializer>(
-        &self,
-        serializer: S,
-    ) -> std::result::Result<S::Ok, S::Error> {
-        Items(self.0.as_slice()).serialize(serializer)
+impl<'a, const LEN: usize, T: Serialize, N: FieldName> Serialize for Items<&'a [T; LEN], N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Items::<&[T], N>::with_name(self.0.as_slice()).serialize(serializer)
     }
 }
 
-impl<'a, const N: usize, T: Serialize> Serialize for Items<&'a [T; N]> {
-    fn serialize<S: serde::Serializer>(
-        &self,
-        serializer: S,
-    ) -> std::result::Result<S::Ok, S::Error> {
-        Items(self.0.as_slice()).serialize(serializer)
+impl<T: Serialize, N: FieldName> Serialize for Items<VecDeque<T>, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for item in &self.0 {
+            seq.serialize_element(&Item::<&T, N>::with_name(item))?;
+        }
+        seq.end()
     }
 }
 
-impl<'a, T: Serialize> Serialize for Items<&'a [T]> {
-    fn serialize<S: serde::Serializer>(
-        &self,
-        serializer: S,
-    ) -> std::result::Result<S::Ok, S::Error> {
+impl<'a, T: Serialize, N: FieldName> Serialize for Items<&'a VecDeque<T>, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
         let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
-        for item in self.0 {
-            seq.serialize_element(&Item(item))?;
+        for item in self.0.iter() {
+            seq.serialize_element(&Item::<&T, N>::with_name(item))?;
         }
         seq.end()
     }
 }
+
+impl<'de, T: Deserialize<'de>, N: FieldName> Deserialize<'de> for Items<Vec<T>, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let items = Vec::<Item<T, N>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|item| item.0)
+            .collect();
+        Ok(Items(items, PhantomData))
+    }
+}
+
+impl<'de, T: Deserialize<'de>, N: FieldName> Deserialize<'de> for Items<VecDeque<T>, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let items = Vec::<Item<T, N>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|item| item.0)
+            .collect();
+        Ok(Items(items, PhantomData))
+    }
+}